@@ -1,31 +1,55 @@
 #![no_std]
-#![feature(raw, unboxed_closures)]
+#![cfg_attr(not(feature = "ptr_metadata"), feature(raw, unboxed_closures))]
+#![cfg_attr(feature = "ptr_metadata", feature(ptr_metadata, layout_for_ptr))]
 /*!
 Provides the `DynSized` trait, which allows conversion between fat pointers and their (meta, data_pointer) components. `derive_DynSized!` may be used to implement `DynSized` for trait objects.
+
+`[T]`, `str`, and the `WrapSized<T>` wrapper for `Sized` types are implemented the same way
+regardless of feature flags, using plain pointer casts; the one exception is how
+`str::disassemble` reads the length. The trait-object machinery is what changes: with the
+`ptr_metadata` feature (nightly only), trait objects are backed by
+[RFC 2580](https://github.com/rust-lang/rfcs/blob/master/text/2580-ptr-meta.md)'s
+`core::ptr::Pointee`/`core::ptr::DynMetadata`, the standard, compiler-blessed way of splitting a
+fat pointer into its metadata and data address, instead of the `core::raw::TraitObject` transmutes
+the non-`ptr_metadata` backend relies on. Either way, each trait object still needs `derive_DynSized!`.
 */
 
 
+#[cfg(not(feature = "ptr_metadata"))]
 extern crate fn_move;
 
-use core::{str, slice, ptr};
+use core::ptr::NonNull;
+use core::str;
+#[cfg(not(feature = "ptr_metadata"))]
+use core::fmt;
+#[cfg(not(feature = "ptr_metadata"))]
+use core::marker::PhantomData;
+#[cfg(not(feature = "ptr_metadata"))]
 use core::raw;
 #[doc(hidden)]
-pub use core::{mem};
+pub use core::mem;
+#[doc(hidden)]
+pub use core::ptr;
 
 /// A trait for dynamically sized types, similar in principle to the `Sized`
 /// trait. Allows conversion between fat and thin pointers.
 ///
 /// The assemble and disassemble methods must be safe, i.e. they must not dereference the raw pointers, only use them to extract the metadata in the case of `disassemble`, or to combine with metadata to produce a fat pointer, in the case of `assemble`.
 ///
-/// # Unsafety
+/// `Data` is the type of the element the data pointer addresses: `T` for `[T]`, `u8` for `str`,
+/// and whatever the wrapped type uses for a struct that forwards to it. This lets callers build
+/// and tear apart fat pointers with a typed data pointer instead of a naked `*const ()`.
+///
+/// # Safety
 ///
 /// The trait is marked `unsafe`, because implementing it wrong can cause undefined behaviour.
 pub unsafe trait DynSized {
     type Meta: Copy;
+    type Data;
 
-    fn assemble(meta: Self::Meta, data: *const ()) -> *const Self;
+    fn assemble(meta: Self::Meta, data: *const Self::Data) -> *const Self;
 
-    fn assemble_mut(meta: Self::Meta, data: *mut ()) -> *mut Self {
+    fn assemble_mut(meta: Self::Meta, data: *mut Self::Data) -> *mut Self {
         unsafe {
             // transmute is safe here, because we're just changing from
             // *const Self to *mut Self
@@ -33,12 +57,12 @@ pub unsafe trait DynSized {
         }
     }
 
-    fn disassemble(ptr: *const Self) -> (Self::Meta, *const ());
+    fn disassemble(ptr: *const Self) -> (Self::Meta, *const Self::Data);
 
-    fn disassemble_mut(ptr: *mut Self) -> (Self::Meta, *mut ()) {
+    fn disassemble_mut(ptr: *mut Self) -> (Self::Meta, *mut Self::Data) {
         let (meta, data) = Self::disassemble(ptr);
         unsafe {
-            (meta, mem::transmute(data))
+            (meta, mem::transmute::<*const Self::Data, *mut Self::Data>(data))
         }
     }
 
@@ -47,61 +71,112 @@ pub unsafe trait DynSized {
         meta
     }
 
-    fn data(&self) -> *const () {
+    fn data(&self) -> *const Self::Data {
         let (_, data) = Self::disassemble(self);
         data
     }
 
-    fn data_mut(&mut self) -> *mut () {
+    fn data_mut(&mut self) -> *mut Self::Data {
         let (_, data) = Self::disassemble_mut(self);
         data
     }
+
+    /// The size, in bytes, of a value with this metadata.
+    ///
+    /// The default falls back to `mem::size_of_val` on a fat pointer assembled with a null data
+    /// address; implementors that can answer without touching the data pointer (e.g. by reading
+    /// a length or a vtable slot) should override this.
+    #[cfg(not(feature = "ptr_metadata"))]
+    fn size_of_val(meta: Self::Meta) -> usize {
+        unsafe { mem::size_of_val(&*Self::assemble(meta, ptr::null())) }
+    }
+
+    /// The alignment, in bytes, of a value with this metadata. See `size_of_val`.
+    #[cfg(not(feature = "ptr_metadata"))]
+    fn align_of_val(meta: Self::Meta) -> usize {
+        unsafe { mem::align_of_val(&*Self::assemble(meta, ptr::null())) }
+    }
+
+    /// The size, in bytes, of a value with this metadata.
+    ///
+    /// The default uses `size_of_val_raw` on a fat pointer assembled with a null data address, so
+    /// it never has to dereference the data pointer; implementors that can answer more cheaply
+    /// (e.g. by reading a length or a vtable slot) should still override this.
+    #[cfg(feature = "ptr_metadata")]
+    fn size_of_val(meta: Self::Meta) -> usize {
+        unsafe { mem::size_of_val_raw(Self::assemble(meta, ptr::null())) }
+    }
+
+    /// The alignment, in bytes, of a value with this metadata. See `size_of_val`.
+    #[cfg(feature = "ptr_metadata")]
+    fn align_of_val(meta: Self::Meta) -> usize {
+        unsafe { mem::align_of_val_raw(Self::assemble(meta, ptr::null())) }
+    }
 }
 
 /// A version of mem::size_of_val that requires only the pointer metadata
 pub fn size_of_val<T>(meta: T::Meta) -> usize where
     T: DynSized + ?Sized
 {
-    unsafe {  mem::size_of_val(&*T::assemble(meta, ptr::null())) }
+    T::size_of_val(meta)
 }
 
 /// A version of mem::align_of_val that requires only the pointer metadata
 pub fn align_of_val<T>(meta: T::Meta) -> usize where
     T: DynSized + ?Sized
 {
-    unsafe {  mem::align_of_val(&*T::assemble(meta, ptr::null())) }
+    T::align_of_val(meta)
 }
 
 /// A wrapper type for `Sized` types that implements `DynSized`.
-/// 
+///
 /// This is unfortunately necessary because a blanket `impl` of `DynSized` for all `Sized` types would conflict with implementations for user-defined structs that are ?Sized.
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct WrapSized<T>(pub T);
 
 unsafe impl<T> DynSized for WrapSized<T> {
     type Meta = ();
+    type Data = T;
 
-    fn assemble(_: (), data: *const ()) -> *const WrapSized<T> {
+    fn assemble(_: (), data: *const T) -> *const WrapSized<T> {
         data as *const WrapSized<T>
     }
 
-    fn disassemble(ptr: *const WrapSized<T>) -> ((), *const ()) {
-        ((), ptr as *const ())
+    fn disassemble(ptr: *const WrapSized<T>) -> ((), *const T) {
+        ((), ptr as *const T)
+    }
+
+    fn size_of_val(_: ()) -> usize {
+        mem::size_of::<T>()
+    }
+
+    fn align_of_val(_: ()) -> usize {
+        mem::align_of::<T>()
     }
 }
 
 unsafe impl<T> DynSized for [T] {
     type Meta = usize;
+    type Data = T;
 
-    fn assemble(len: usize, data: *const ()) -> *const [T] {
-        unsafe {
-            slice::from_raw_parts(data as *const T, len)
-        }
+    fn assemble(len: usize, data: *const T) -> *const [T] {
+        // `ptr::slice_from_raw_parts`, unlike `slice::from_raw_parts`, never constructs a
+        // reference, so this stays sound even when `data` is null or dangling.
+        ptr::slice_from_raw_parts(data, len)
+    }
+
+    fn disassemble(slice: *const [T]) -> (usize, *const T) {
+        // `<*const [T]>::len()` reads the pointer's own metadata, not the pointee, so this never
+        // has to construct a reference (and stays sound for a null/dangling `slice`).
+        (slice.len(), slice as *const T)
+    }
+
+    fn size_of_val(len: usize) -> usize {
+        len * mem::size_of::<T>()
     }
 
-    fn disassemble(slice: *const [T]) -> (usize, *const ()) {
-        let slice = unsafe { &*slice };
-        (slice.len(), slice.as_ptr() as *const ())
+    fn align_of_val(_: usize) -> usize {
+        mem::align_of::<T>()
     }
 }
 
@@ -117,17 +192,33 @@ fn test_slice() {
 
 unsafe impl DynSized for str {
     type Meta = usize;
+    type Data = u8;
 
-    fn assemble(len: usize, data: *const ()) -> *const str {
-        unsafe {
-            str::from_utf8_unchecked(slice::from_raw_parts(data as *const u8, len))
-        }
+    fn assemble(len: usize, data: *const u8) -> *const str {
+        // `[u8]::assemble` already avoids constructing a reference from `data`, so building the
+        // `*const str` through it (same layout: `str` is just `[u8]` with a UTF-8 invariant) keeps
+        // this sound for a null/dangling `data` too.
+        unsafe { mem::transmute::<*const [u8], *const str>(<[u8]>::assemble(len, data)) }
     }
 
-    fn disassemble(s: *const str) -> (usize, *const ()) {
-        unsafe {
-            DynSized::disassemble((&*s).as_bytes())
-        }
+    fn disassemble(s: *const str) -> (usize, *const u8) {
+        // `ptr::metadata` reads the pointer's own length word, never the pointee, so this stays
+        // reference-free on the backend that has it; the other backend has no metadata-only way
+        // to ask a `*const str` its length, so it has to go through a real reference.
+        #[cfg(feature = "ptr_metadata")]
+        let len = ptr::metadata(s);
+        #[cfg(not(feature = "ptr_metadata"))]
+        let len = unsafe { (&*s).len() };
+
+        (len, s as *const u8)
+    }
+
+    fn size_of_val(len: usize) -> usize {
+        len
+    }
+
+    fn align_of_val(_: usize) -> usize {
+        1
     }
 }
 
@@ -141,52 +232,127 @@ fn test_str() {
     assert_eq!(s, new_s);
 }
 
-#[derive(Copy, Clone)]
+#[cfg(not(feature = "ptr_metadata"))]
 #[doc(hidden)]
-pub struct TraitObject(raw::TraitObject);
+pub struct TraitObject<Dyn: ?Sized>(raw::TraitObject, PhantomData<Dyn>);
+
+#[cfg(not(feature = "ptr_metadata"))]
+impl<Dyn: ?Sized> Copy for TraitObject<Dyn> {}
 
-#[derive(Copy, Clone, Debug)]
+#[cfg(not(feature = "ptr_metadata"))]
+impl<Dyn: ?Sized> Clone for TraitObject<Dyn> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+/// The vtable-derived metadata of a `dyn Dyn` trait object.
+///
+/// This is the `DynSized::Meta` used for trait objects. It mirrors the first three words of the
+/// vtable the compiler emits for every `dyn Dyn`: the `drop_in_place` function pointer, the size,
+/// and the alignment. Reading them out this way lets `size_of_val`/`align_of_val` and
+/// `drop_in_place` work without ever constructing a fat pointer with a dangling data address.
+#[cfg(not(feature = "ptr_metadata"))]
 #[doc(hidden)]
-pub struct Vtable(*mut ());
+pub struct DynMetadata<Dyn: ?Sized> {
+    vtable: *const (),
+    _marker: PhantomData<Dyn>,
+}
+
+#[cfg(not(feature = "ptr_metadata"))]
+impl<Dyn: ?Sized> Copy for DynMetadata<Dyn> {}
+
+#[cfg(not(feature = "ptr_metadata"))]
+impl<Dyn: ?Sized> Clone for DynMetadata<Dyn> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+#[cfg(not(feature = "ptr_metadata"))]
+impl<Dyn: ?Sized> fmt::Debug for DynMetadata<Dyn> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("DynMetadata").field(&self.vtable).finish()
+    }
+}
+
+#[cfg(not(feature = "ptr_metadata"))]
+impl<Dyn: ?Sized> DynMetadata<Dyn> {
+    /// The size, in bytes, of the value this metadata was produced from.
+    pub fn size_of(self) -> usize {
+        unsafe { *(self.vtable as *const usize).offset(1) }
+    }
+
+    /// The alignment, in bytes, of the value this metadata was produced from.
+    pub fn align_of(self) -> usize {
+        unsafe { *(self.vtable as *const usize).offset(2) }
+    }
 
-impl TraitObject {
-    pub fn construct(vtable: Vtable, data: *mut ()) -> TraitObject {
+    /// Runs the destructor of the value this metadata was produced from.
+    ///
+    /// # Safety
+    ///
+    /// `data` must point to a live, properly aligned value of the erased concrete type that this
+    /// metadata's vtable was built for.
+    pub unsafe fn drop_in_place(self, data: *mut ()) {
+        let drop_in_place: unsafe fn(*mut ()) =
+            mem::transmute(*(self.vtable as *const *const ()));
+        drop_in_place(data)
+    }
+}
+
+#[cfg(not(feature = "ptr_metadata"))]
+impl<Dyn: ?Sized> TraitObject<Dyn> {
+    pub fn construct(vtable: DynMetadata<Dyn>, data: *mut ()) -> TraitObject<Dyn> {
         TraitObject(raw::TraitObject {
             data: data,
-            vtable: vtable.0,
-        })
+            vtable: vtable.vtable as *mut (),
+        }, PhantomData)
     }
 
     pub fn data(self) -> *mut () {
         self.0.data
     }
 
-    pub fn vtable(self) -> Vtable {
-        Vtable(self.0.vtable)
+    pub fn vtable(self) -> DynMetadata<Dyn> {
+        DynMetadata {
+            vtable: self.0.vtable as *const (),
+            _marker: PhantomData,
+        }
     }
 }
 
+#[cfg(not(feature = "ptr_metadata"))]
 #[macro_export]
 #[doc(hidden)]
 macro_rules! __derive_DynSized_body {
     ($Trait:ty) => {
-        type Meta = $crate::Vtable;
+        type Meta = $crate::DynMetadata<$Trait>;
+        type Data = ();
 
-        fn assemble(vtable: $crate::Vtable, data: *const ()) -> *const Self {
+        fn assemble(vtable: $crate::DynMetadata<$Trait>, data: *const ()) -> *const Self {
             unsafe {
                 $crate::mem::transmute(
-                    $crate::TraitObject::construct(vtable, data as *mut ())
+                    $crate::TraitObject::<$Trait>::construct(vtable, data as *mut ())
                 )
             }
         }
 
         fn disassemble(ptr: *const Self) -> (Self::Meta, *const ()) {
-            let trait_object: $crate::TraitObject = unsafe {
+            let trait_object: $crate::TraitObject<$Trait> = unsafe {
                 $crate::mem::transmute(ptr)
             };
 
             (trait_object.vtable(), trait_object.data())
         }
+
+        fn size_of_val(meta: Self::Meta) -> usize {
+            meta.size_of()
+        }
+
+        fn align_of_val(meta: Self::Meta) -> usize {
+            meta.align_of()
+        }
     };
 }
 
@@ -208,6 +374,9 @@ macro_rules! __derive_DynSized_body {
 /// # }
 /// ```
 ///
+/// With the `ptr_metadata` feature enabled, the same macro and call syntax are available, backed
+/// by `core::ptr::DynMetadata` instead of the hand-rolled vtable reader above.
+#[cfg(not(feature = "ptr_metadata"))]
 #[macro_export]
 macro_rules! derive_DynSized {
     ($Trait:ty) => {
@@ -223,22 +392,92 @@ macro_rules! derive_DynSized {
     };
 }
 
+/// The `ptr_metadata` backend's version of `__derive_DynSized_body!`: `Self::Meta` is the real
+/// `core::ptr::DynMetadata<dyn $Trait>`, and `assemble`/`disassemble` are just
+/// `ptr::from_raw_parts`/`ptr::metadata`, with no transmute through `core::raw::TraitObject`.
+#[cfg(feature = "ptr_metadata")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __derive_DynSized_body_ptr_metadata {
+    ($Trait:path) => {
+        type Meta = $crate::ptr::DynMetadata<dyn $Trait>;
+        type Data = ();
+
+        fn assemble(meta: Self::Meta, data: *const ()) -> *const Self {
+            $crate::ptr::from_raw_parts(data, meta)
+        }
+
+        fn disassemble(ptr: *const Self) -> (Self::Meta, *const ()) {
+            ($crate::ptr::metadata(ptr), ptr as *const ())
+        }
+
+        fn size_of_val(meta: Self::Meta) -> usize {
+            meta.size_of()
+        }
+
+        fn align_of_val(meta: Self::Meta) -> usize {
+            meta.align_of()
+        }
+    };
+}
+
+/// Derives the `DynSized` trait for trait objects, on top of `core::ptr::DynMetadata`.
+///
+/// Same call syntax as the non-`ptr_metadata` backend's `derive_DynSized!` (see its docs), but `$Trait`
+/// names the bare trait, not the trait object type: the macro adds the `dyn` itself, since
+/// `core::ptr::Pointee::Metadata` for a trait object is always `DynMetadata<dyn $Trait>`.
+#[cfg(feature = "ptr_metadata")]
+#[macro_export]
+macro_rules! derive_DynSized {
+    ($Trait:path) => {
+        unsafe impl $crate::DynSized for dyn $Trait {
+            __derive_DynSized_body_ptr_metadata!($Trait);
+        }
+    };
+
+    ($Trait:path, $($args:tt)+ ) => {
+        unsafe impl<$($args)+> $crate::DynSized for dyn $Trait {
+            __derive_DynSized_body_ptr_metadata!($Trait);
+        }
+    };
+}
+
+#[cfg(not(feature = "ptr_metadata"))]
+use core::any::Any;
+#[cfg(feature = "ptr_metadata")]
 use core::any::Any;
+#[cfg(not(feature = "ptr_metadata"))]
 use fn_move::FnMove;
 
+#[cfg(feature = "ptr_metadata")]
 derive_DynSized!(Any);
+
+#[cfg(not(feature = "ptr_metadata"))]
+derive_DynSized!(Any);
+#[cfg(not(feature = "ptr_metadata"))]
 derive_DynSized!(Any + Send);
+#[cfg(not(feature = "ptr_metadata"))]
 derive_DynSized!(Fn<Args, Output=Output> + 'a, 'a, Args, Output);
+#[cfg(not(feature = "ptr_metadata"))]
 derive_DynSized!(Fn<Args, Output=Output> + Send + 'a, 'a, Args, Output);
+#[cfg(not(feature = "ptr_metadata"))]
 derive_DynSized!(Fn<Args, Output=Output> + Sync + 'a, 'a, Args, Output);
+#[cfg(not(feature = "ptr_metadata"))]
 derive_DynSized!(Fn<Args, Output=Output> + Send + Sync + 'a, 'a, Args, Output);
+#[cfg(not(feature = "ptr_metadata"))]
 derive_DynSized!(FnMut<Args, Output=Output> + 'a, 'a, Args, Output);
+#[cfg(not(feature = "ptr_metadata"))]
 derive_DynSized!(FnMut<Args, Output=Output> + Send + 'a, 'a, Args, Output);
+#[cfg(not(feature = "ptr_metadata"))]
 derive_DynSized!(FnOnce<Args, Output=Output> + 'a, 'a, Args, Output);
+#[cfg(not(feature = "ptr_metadata"))]
 derive_DynSized!(FnOnce<Args, Output=Output> + Send + 'a, 'a, Args, Output);
+#[cfg(not(feature = "ptr_metadata"))]
 derive_DynSized!(FnMove<Args, Output=Output> + 'a, 'a, Args, Output);
+#[cfg(not(feature = "ptr_metadata"))]
 derive_DynSized!(FnMove<Args, Output=Output> + Send + 'a, 'a, Args, Output);
 
+#[cfg(not(feature = "ptr_metadata"))]
 #[test]
 #[allow(non_snake_case)]
 fn test_derive_DynSized() {
@@ -247,6 +486,108 @@ fn test_derive_DynSized() {
     derive_DynSized!(MyBorrow<Borrowed>, Borrowed);
 }
 
+#[cfg(not(feature = "ptr_metadata"))]
+#[test]
+fn test_DynMetadata_size_and_align() {
+    use core::any::Any;
+
+    let x: i64 = 42;
+    let any_ref: &Any = &x;
+    let meta = any_ref.meta();
+    assert_eq!(size_of_val::<Any>(meta), mem::size_of::<i64>());
+    assert_eq!(align_of_val::<Any>(meta), mem::align_of::<i64>());
+}
+
+#[cfg(not(feature = "ptr_metadata"))]
+#[test]
+fn test_DynMetadata_drop_in_place() {
+    use core::any::Any;
+    use core::sync::atomic::{AtomicBool, Ordering};
+
+    static DROPPED: AtomicBool = AtomicBool::new(false);
+
+    struct Droppy;
+    impl Drop for Droppy {
+        fn drop(&mut self) {
+            DROPPED.store(true, Ordering::SeqCst);
+        }
+    }
+
+    let mut value = Droppy;
+    let any_ptr = &mut value as &mut Any as *mut Any;
+    let (meta, data) = DynSized::disassemble_mut(any_ptr);
+    unsafe {
+        meta.drop_in_place(data);
+    }
+    mem::forget(value);
+
+    assert!(DROPPED.load(Ordering::SeqCst));
+}
+
+#[cfg(feature = "ptr_metadata")]
+#[test]
+#[allow(non_snake_case)]
+fn test_derive_DynSized_ptr_metadata() {
+    use core::borrow::Borrow;
+    trait MyBorrow<Borrowed>: Borrow<Borrowed> {}
+    derive_DynSized!(MyBorrow<Borrowed>, Borrowed);
+}
+
+#[cfg(feature = "ptr_metadata")]
+#[test]
+fn test_trait_object_ptr_metadata() {
+    use core::any::Any;
+
+    let x: i32 = 5;
+    let any = &x as &dyn Any;
+    let (meta, data) = DynSized::disassemble(any as *const dyn Any);
+    let new_any: &dyn Any = unsafe { &*DynSized::assemble(meta, data) };
+    assert_eq!(new_any.downcast_ref::<i32>(), Some(&5));
+}
+
+#[cfg(feature = "ptr_metadata")]
+#[test]
+#[allow(non_snake_case)]
+fn test_DynMetadata_size_and_align_ptr_metadata() {
+    use core::any::Any;
+
+    let x: i64 = 42;
+    let any_ref: &dyn Any = &x;
+    let meta = any_ref.meta();
+    assert_eq!(size_of_val::<dyn Any>(meta), mem::size_of::<i64>());
+    assert_eq!(align_of_val::<dyn Any>(meta), mem::align_of::<i64>());
+}
+
+#[cfg(feature = "ptr_metadata")]
+#[test]
+#[allow(non_snake_case)]
+fn test_DynMetadata_drop_in_place_ptr_metadata() {
+    use core::any::Any;
+    use core::sync::atomic::{AtomicBool, Ordering};
+
+    static DROPPED: AtomicBool = AtomicBool::new(false);
+
+    struct Droppy;
+    impl Drop for Droppy {
+        fn drop(&mut self) {
+            DROPPED.store(true, Ordering::SeqCst);
+        }
+    }
+
+    let mut value = Droppy;
+    // core::ptr::DynMetadata has no drop_in_place method of its own; reconstructing the fat
+    // pointer and calling the free function is the sound way to run the destructor from just
+    // the metadata and a data address.
+    let any_ptr = &mut value as &mut dyn Any as *mut dyn Any;
+    let (meta, data) = DynSized::disassemble_mut(any_ptr);
+    unsafe {
+        ptr::drop_in_place(<dyn Any as DynSized>::assemble_mut(meta, data));
+    }
+    mem::forget(value);
+
+    assert!(DROPPED.load(Ordering::SeqCst));
+}
+
 /// An extension trait adding .meta() and .data() convenience methods
 /// to built-in pointer types
 pub trait PtrExt {
@@ -254,12 +595,12 @@ pub trait PtrExt {
 
     fn meta(&self) -> <Self::Referent as DynSized>::Meta;
 
-    fn data(&self) -> *const ();
+    fn data(&self) -> *const <Self::Referent as DynSized>::Data;
 }
 
 /// adds the `data_mut` method to `*mut T`
 pub trait PtrMutExt: PtrExt {
-    fn data_mut(&self) -> *mut ();
+    fn data_mut(&self) -> *mut <Self::Referent as DynSized>::Data;
 }
 
 impl<T: DynSized + ?Sized> PtrExt for *const T {
@@ -270,7 +611,7 @@ impl<T: DynSized + ?Sized> PtrExt for *const T {
         meta
     }
 
-    fn data(&self) -> *const () {
+    fn data(&self) -> *const T::Data {
         let (_, data) = T::disassemble(*self);
         data
     }
@@ -283,7 +624,7 @@ impl<T: DynSized + ?Sized> PtrExt for *mut T {
         (*self as *const T).meta()
     }
 
-    fn data(&self) -> *const () {
+    fn data(&self) -> *const T::Data {
         let (_, data) = T::disassemble(*self);
         data
     }
@@ -291,7 +632,7 @@ impl<T: DynSized + ?Sized> PtrExt for *mut T {
 
 impl<T: DynSized + ?Sized> PtrMutExt for *mut T {
 
-    fn data_mut(&self) -> *mut () {
+    fn data_mut(&self) -> *mut T::Data {
         let (_, data) = T::disassemble_mut(*self);
         data
     }
@@ -311,17 +652,142 @@ fn test_PtrExt() {
     let len: <[i32] as DynSized>::Meta = (slice as *const [i32]).meta();
     assert_eq!(len, 3usize);
 
-    let data: *const () = slice.data();
-    assert_eq!(slice as *const [_] as *const (), data);
-    let data: *const () = (slice as &[i32]).data();
-    assert_eq!(slice as *const [_] as *const (), data);
-    let data: *const () = (slice as *const [i32]).data();
-    assert_eq!(slice as *const [_] as *const (), data);
-    let data: *const () = (slice as *mut [i32]).data();
-    assert_eq!(slice as *const [_] as *const (), data);
-
-    let data: *mut () = slice.data_mut();
-    assert_eq!(slice as *mut [_] as *mut (), data);
-    let data: *mut () = (slice as *mut [i32]).data_mut();
-    assert_eq!(slice as *mut [_] as *mut (), data);
+    let data: *const i32 = slice.data();
+    assert_eq!(slice as *const [_] as *const i32, data);
+    let data: *const i32 = (slice as &[i32]).data();
+    assert_eq!(slice as *const [_] as *const i32, data);
+    let data: *const i32 = (slice as *const [i32]).data();
+    assert_eq!(slice as *const [_] as *const i32, data);
+    let data: *const i32 = (slice as *mut [i32]).data();
+    assert_eq!(slice as *const [_] as *const i32, data);
+
+    let data: *mut i32 = slice.data_mut();
+    assert_eq!(slice as *mut [_] as *mut i32, data);
+    let data: *mut i32 = (slice as *mut [i32]).data_mut();
+    assert_eq!(slice as *mut [_] as *mut i32, data);
+}
+
+/// A `DynSized::Meta` paired with an opaque storage token `S`, for collections that keep the
+/// pointer metadata and the data address in separate places (an inline buffer, a shared-memory
+/// arena, an index-based handle). `S` defaults to `()` for storages that don't need a token of
+/// their own.
+///
+/// A `Handle` never holds a live fat pointer: call `resolve` once the storage has turned the
+/// token into a real data address.
+pub struct Handle<T: DynSized + ?Sized, S = ()> {
+    meta: T::Meta,
+    token: S,
+}
+
+impl<T: DynSized + ?Sized, S> Handle<T, S> {
+    pub fn new(meta: T::Meta, token: S) -> Handle<T, S> {
+        Handle { meta, token }
+    }
+
+    pub fn meta(&self) -> T::Meta {
+        self.meta
+    }
+
+    pub fn token(&self) -> &S {
+        &self.token
+    }
+
+    pub fn token_mut(&mut self) -> &mut S {
+        &mut self.token
+    }
+
+    /// Materializes a fat pointer once the storage has resolved this handle's token to a real
+    /// data address.
+    ///
+    /// # Safety
+    ///
+    /// `data` must be a valid data pointer for a value with this handle's metadata.
+    pub unsafe fn resolve(&self, data: *mut T::Data) -> *mut T {
+        T::assemble_mut(self.meta, data)
+    }
+}
+
+/// Records the metadata produced by a genuine unsizing coercion (e.g. `[u8; 4] -> [u8]`,
+/// `Concrete -> Trait`) into a new `Handle` with the given `token`. The coercion happens on
+/// `value`, a reference the caller passes in already coerced (e.g. `&[u8; 4]` passed where a
+/// `&[u8]` is expected); the returned handle itself never holds a live fat pointer. An existing
+/// `Handle` is not converted: there is no conversion from a `Handle` of one type to another.
+pub fn coerce<T: DynSized + ?Sized, S>(value: &T, token: S) -> Handle<T, S> {
+    let (meta, _) = T::disassemble(value as *const T);
+    Handle::new(meta, token)
+}
+
+#[test]
+fn test_handle() {
+    let array = [1u8, 2, 3, 4];
+    let handle: Handle<[u8], usize> = coerce(&array, 0);
+
+    let mut storage = array;
+    let data = storage.as_mut_ptr();
+    let resolved: &mut [u8] = unsafe { &mut *handle.resolve(data) };
+    assert_eq!(resolved, &array);
+}
+
+/// A marker for `DynSized` types whose pointers are thin, i.e. `Meta = ()`.
+pub trait Thin: DynSized<Meta = ()> {}
+
+impl<T: DynSized<Meta = ()> + ?Sized> Thin for T {}
+
+/// Builds a well-formed-but-unallocated `*const T` from just the pointer metadata, mirroring
+/// `core::ptr::null` generalized to DSTs.
+pub fn null<T: DynSized + ?Sized>(meta: T::Meta) -> *const T {
+    T::assemble(meta, ptr::null())
+}
+
+/// Builds a well-formed-but-unallocated `*mut T` from just the pointer metadata, mirroring
+/// `core::ptr::null_mut` generalized to DSTs.
+pub fn null_mut<T: DynSized + ?Sized>(meta: T::Meta) -> *mut T {
+    T::assemble_mut(meta, ptr::null_mut())
+}
+
+/// Builds a well-formed-but-unallocated `NonNull<T>` from just the pointer metadata, mirroring
+/// `NonNull::dangling` generalized to DSTs: the data address is placed at the type's alignment,
+/// which is always nonzero.
+pub fn dangling<T: DynSized + ?Sized>(meta: T::Meta) -> NonNull<T> {
+    let data = align_of_val::<T>(meta) as *mut T::Data;
+    unsafe { NonNull::new_unchecked(T::assemble_mut(meta, data)) }
+}
+
+/// An extension trait adding `.meta()`/`.data()`/`.data_mut()` to `NonNull<T>`, analogous to
+/// `PtrExt`/`PtrMutExt`.
+pub trait NonNullExt {
+    type Referent: DynSized + ?Sized;
+
+    fn meta(&self) -> <Self::Referent as DynSized>::Meta;
+
+    fn data(&self) -> *const <Self::Referent as DynSized>::Data;
+
+    fn data_mut(&self) -> *mut <Self::Referent as DynSized>::Data;
+}
+
+impl<T: DynSized + ?Sized> NonNullExt for NonNull<T> {
+    type Referent = T;
+
+    fn meta(&self) -> T::Meta {
+        (self.as_ptr() as *const T).meta()
+    }
+
+    fn data(&self) -> *const T::Data {
+        (self.as_ptr() as *const T).data()
+    }
+
+    fn data_mut(&self) -> *mut T::Data {
+        self.as_ptr().data_mut()
+    }
+}
+
+#[test]
+fn test_null_and_dangling() {
+    let slice_null: *const [i32] = null(3);
+    assert_eq!(slice_null.data(), ptr::null());
+    assert_eq!(slice_null.meta(), 3usize);
+
+    let slice_dangling = dangling::<[i32]>(3);
+    assert_eq!(slice_dangling.data() as usize, mem::align_of::<i32>());
+    assert_eq!(slice_dangling.meta(), 3usize);
 }
@@ -1,3 +1,5 @@
+#![cfg_attr(feature = "ptr_metadata", feature(ptr_metadata))]
+
 #[macro_use]
 extern crate dyn_sized;
 
@@ -5,6 +7,7 @@ trait Foo {}
 derive_DynSized!(Foo);
 
 trait MyTrait<'a, T: 'a> {
+    #[allow(dead_code)]
     fn borrow_it(&self, arg: &'a T);
 }
 derive_DynSized!(MyTrait<'a, T>, 'a, T: 'a);
@@ -1,4 +1,3 @@
-
 extern crate dyn_sized;
 
 use dyn_sized::DynSized;
@@ -13,8 +12,9 @@ struct MyStruct<T: ?Sized> {
 
 unsafe impl<T: DynSized + ?Sized> DynSized for MyStruct<T> {
     type Meta = T::Meta;
+    type Data = T::Data;
 
-    fn assemble(meta: T::Meta, data: *const ()) -> *const Self {
+    fn assemble(meta: T::Meta, data: *const T::Data) -> *const Self {
         // note: safe because T::assemble does not dereference *data
         let t_ptr: *const T = T::assemble(meta, data);
         unsafe {
@@ -23,7 +23,7 @@ unsafe impl<T: DynSized + ?Sized> DynSized for MyStruct<T> {
         }
     }
 
-    fn disassemble(ptr: *const Self) -> (T::Meta, *const ()) {
+    fn disassemble(ptr: *const Self) -> (T::Meta, *const T::Data) {
         let t_ptr: *mut T = unsafe {
             // again, this is safe because of the way the compiler represents pointers to unsized structs
             mem::transmute(ptr)
@@ -43,9 +43,9 @@ fn slice() {
 
     assert_eq!(my_struct_ptr.meta(), 4);
     assert_eq!(&my_struct_ptr.value, &[1,2,3,4]);
-    assert_eq!(my_struct_ptr.data(), &my_struct as *const _ as *const ());
+    assert_eq!(my_struct_ptr.data(), &my_struct as *const _ as *const i32);
 
-    let my_struct_ptr_assembled = MyStruct::assemble(4usize, &my_struct as *const _ as *const ());
+    let my_struct_ptr_assembled = MyStruct::assemble(4usize, &my_struct as *const _ as *const i32);
 
     assert_eq!(my_struct_ptr as *const _, my_struct_ptr_assembled);
 }
@@ -67,5 +67,5 @@ fn trait_object() {
         value: 3i32
     };
 
-    assert_eq!((&my_struct as &MyStruct<Foo>).value.foo(), 4);
+    assert_eq!((&my_struct as &MyStruct<dyn Foo>).value.foo(), 4);
 }